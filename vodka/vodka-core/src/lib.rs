@@ -1,8 +1,22 @@
-use std::ffi::CStr;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
 use std::path::PathBuf;
 use std::ptr;
 
+#[cfg(unix)]
+fn c_path_to_path(path: *const c_char) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStrExt;
+
+    if path.is_null() {
+        return None;
+    }
+
+    let c_str = unsafe { CStr::from_ptr(path) };
+    Some(PathBuf::from(std::ffi::OsStr::from_bytes(c_str.to_bytes())))
+}
+
+#[cfg(not(unix))]
 fn c_path_to_path(path: *const c_char) -> Option<PathBuf> {
     if path.is_null() {
         return None;
@@ -12,36 +26,133 @@ fn c_path_to_path(path: *const c_char) -> Option<PathBuf> {
     Some(PathBuf::from(c_str.to_string_lossy().into_owned()))
 }
 
-fn inspect_path(path: *const c_char) -> Option<(u16, u16, u32)> {
-    let path = c_path_to_path(path)?;
-    vodka_pe::inspect_pe_path(&path)
+/// Outcome of a `vodka_pe_*` FFI call, returned as an `i32` so a C caller
+/// can distinguish a missing file from a malformed PE from a panic, instead
+/// of collapsing everything into a single `bool`.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VodkaPeStatus {
+    Ok = 0,
+    NotFound = 1,
+    ReadError = 2,
+    NotPe = 3,
+    UnsupportedMagic = 4,
+    Truncated = 5,
+    Panicked = 6,
+}
+
+impl VodkaPeStatus {
+    fn message(self) -> &'static str {
+        match self {
+            VodkaPeStatus::Ok => "ok",
+            VodkaPeStatus::NotFound => "file not found",
+            VodkaPeStatus::ReadError => "failed to read file",
+            VodkaPeStatus::NotPe => "not a PE image",
+            VodkaPeStatus::UnsupportedMagic => "unsupported optional header magic",
+            VodkaPeStatus::Truncated => "PE headers truncated",
+            VodkaPeStatus::Panicked => "panic while inspecting PE file",
+        }
+    }
+}
+
+impl From<vodka_pe::PeParseError> for VodkaPeStatus {
+    fn from(error: vodka_pe::PeParseError) -> Self {
+        match error {
+            vodka_pe::PeParseError::Truncated => VodkaPeStatus::Truncated,
+            vodka_pe::PeParseError::NotPe => VodkaPeStatus::NotPe,
+            vodka_pe::PeParseError::UnsupportedMagic => VodkaPeStatus::UnsupportedMagic,
+        }
+    }
+}
+
+impl From<vodka_pe::PeOpenError> for VodkaPeStatus {
+    fn from(error: vodka_pe::PeOpenError) -> Self {
+        match error {
+            vodka_pe::PeOpenError::Io(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => {
+                VodkaPeStatus::NotFound
+            }
+            vodka_pe::PeOpenError::Io(_) => VodkaPeStatus::ReadError,
+            vodka_pe::PeOpenError::Parse(parse_error) => parse_error.into(),
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
 }
 
+fn set_last_error(message: &str) {
+    let cstring = CString::new(message).unwrap_or_else(|_| CString::new("<error message contained NUL>").unwrap());
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(cstring));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+}
+
+/// Returns a pointer to the last error message recorded on this thread, or
+/// null if the most recent call succeeded or no call has happened yet. The
+/// pointer stays valid until the next `vodka_pe_*` call on the same thread.
 #[no_mangle]
-pub extern "C" fn vodka_pe_inspect(
+pub extern "C" fn vodka_pe_last_error() -> *const c_char {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map(|message| message.as_ptr())
+            .unwrap_or(ptr::null())
+    })
+}
+
+fn inspect_path_detailed(path: *const c_char) -> Result<(u16, u16, u32), VodkaPeStatus> {
+    let path = c_path_to_path(path).ok_or(VodkaPeStatus::NotFound)?;
+    let info = vodka_pe::inspect_pe_info_path_detailed(&path)?;
+    Ok((info.machine, info.subsystem, info.entry_point_rva))
+}
+
+#[no_mangle]
+pub extern "C" fn vodka_pe_inspect_ex(
     path: *const c_char,
     machine: *mut u16,
     subsystem: *mut u16,
     entry_point_rva: *mut u32,
-) -> bool {
-    let result = std::panic::catch_unwind(|| inspect_path(path));
-    let Some((machine_value, subsystem_value, entry_point_value)) = result.ok().flatten() else {
-        return false;
-    };
-
-    unsafe {
-        if !machine.is_null() {
-            *machine = machine_value;
+) -> i32 {
+    let status = match std::panic::catch_unwind(|| inspect_path_detailed(path)) {
+        Ok(Ok((machine_value, subsystem_value, entry_point_value))) => {
+            unsafe {
+                if !machine.is_null() {
+                    *machine = machine_value;
+                }
+                if !subsystem.is_null() {
+                    *subsystem = subsystem_value;
+                }
+                if !entry_point_rva.is_null() {
+                    *entry_point_rva = entry_point_value;
+                }
+            }
+            clear_last_error();
+            VodkaPeStatus::Ok
         }
-        if !subsystem.is_null() {
-            *subsystem = subsystem_value;
+        Ok(Err(status)) => {
+            set_last_error(status.message());
+            status
         }
-        if !entry_point_rva.is_null() {
-            *entry_point_rva = entry_point_value;
+        Err(_) => {
+            set_last_error(VodkaPeStatus::Panicked.message());
+            VodkaPeStatus::Panicked
         }
-    }
+    };
+
+    status as i32
+}
 
-    true
+#[no_mangle]
+pub extern "C" fn vodka_pe_inspect(
+    path: *const c_char,
+    machine: *mut u16,
+    subsystem: *mut u16,
+    entry_point_rva: *mut u32,
+) -> bool {
+    vodka_pe_inspect_ex(path, machine, subsystem, entry_point_rva) == VodkaPeStatus::Ok as i32
 }
 
 #[no_mangle]
@@ -1,49 +1,296 @@
-use std::fs;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
 use std::path::Path;
 
-fn read_u16_le(bytes: &[u8], offset: usize) -> Option<u16> {
-    let slice = bytes.get(offset..offset + 2)?;
-    Some(u16::from_le_bytes([slice[0], slice[1]]))
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+
+const IMAGE_DIRECTORY_ENTRY_IMPORT: usize = 1;
+const IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR: usize = 14;
+const IMAGE_NUMBEROF_DIRECTORY_ENTRIES: usize = 16;
+const SECTION_HEADER_SIZE: usize = 40;
+const IMPORT_DESCRIPTOR_SIZE: u64 = 20;
+const MAX_IMPORT_NAME_LEN: usize = 260;
+
+/// Why a PE header failed to parse, as distinct from an I/O failure while
+/// opening the underlying file (see [`PeOpenError`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeParseError {
+    /// The source ran out of bytes somewhere a complete header was expected.
+    Truncated,
+    /// The `PE\0\0` signature was missing at the offset the DOS header points to.
+    NotPe,
+    /// The optional header's magic was neither PE32 (`0x10B`) nor PE32+ (`0x20B`).
+    UnsupportedMagic,
 }
 
-fn read_u32_le(bytes: &[u8], offset: usize) -> Option<u32> {
-    let slice = bytes.get(offset..offset + 4)?;
-    Some(u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]))
+/// Failure mode for path-based inspection: either the file could not be
+/// opened, or it opened fine but isn't a well-formed PE image. A read that
+/// fails after the file is open is reported as `Parse(PeParseError::Truncated)`.
+#[derive(Debug)]
+pub enum PeOpenError {
+    Io(std::io::Error),
+    Parse(PeParseError),
 }
 
-pub fn inspect_pe_bytes(bytes: &[u8]) -> Option<(u16, u16, u32)> {
-    if bytes.len() < 0x40 {
-        return None;
+/// Types that can be parsed out of any `Read + Seek` source, so large
+/// binaries never need to be fully buffered just to read a few header
+/// fields.
+pub trait FromReader: Sized {
+    fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<Self, PeParseError>;
+}
+
+fn read_u16_le<R: Read>(reader: &mut R) -> Result<u16, PeParseError> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf).map_err(|_| PeParseError::Truncated)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32_le<R: Read>(reader: &mut R) -> Result<u32, PeParseError> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf).map_err(|_| PeParseError::Truncated)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn seek_to<R: Seek>(reader: &mut R, offset: u64) -> Result<u64, PeParseError> {
+    reader.seek(SeekFrom::Start(offset)).map_err(|_| PeParseError::Truncated)
+}
+
+fn read_cstr_ascii<R: Read + Seek>(reader: &mut R, offset: u64) -> Option<String> {
+    seek_to(reader, offset).ok()?;
+
+    let mut name = Vec::new();
+    let mut byte = [0u8; 1];
+    for _ in 0..MAX_IMPORT_NAME_LEN {
+        reader.read_exact(&mut byte).ok()?;
+        if byte[0] == 0 {
+            return Some(String::from_utf8_lossy(&name).into_owned());
+        }
+        name.push(byte[0]);
     }
 
-    let pe_offset = read_u32_le(bytes, 0x3C)? as usize;
-    if bytes.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
-        return None;
+    None
+}
+
+/// One entry of the PE optional header's data directory array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DataDirectory {
+    pub rva: u32,
+    pub size: u32,
+}
+
+/// A single section table entry, enough to map an RVA to a file offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionHeader {
+    pub name: String,
+    pub virtual_size: u32,
+    pub virtual_address: u32,
+    pub raw_size: u32,
+    pub raw_address: u32,
+}
+
+impl SectionHeader {
+    fn read_at<R: Read + Seek>(reader: &mut R, offset: u64) -> Result<SectionHeader, PeParseError> {
+        seek_to(reader, offset)?;
+
+        let mut raw_name = [0u8; 8];
+        reader.read_exact(&mut raw_name).map_err(|_| PeParseError::Truncated)?;
+        let name_end = raw_name.iter().position(|&b| b == 0).unwrap_or(8);
+        let name = String::from_utf8_lossy(&raw_name[..name_end]).into_owned();
+
+        Ok(SectionHeader {
+            name,
+            virtual_size: read_u32_le(reader)?,
+            virtual_address: read_u32_le(reader)?,
+            raw_size: read_u32_le(reader)?,
+            raw_address: read_u32_le(reader)?,
+        })
+    }
+
+    fn contains_rva(&self, rva: u32) -> bool {
+        let span = self.virtual_size.max(self.raw_size);
+        rva >= self.virtual_address && rva < self.virtual_address.saturating_add(span)
     }
+}
 
-    let coff_offset = pe_offset + 4;
-    let machine = read_u16_le(bytes, coff_offset)?;
-    let optional_size = read_u16_le(bytes, coff_offset + 16)? as usize;
-    let optional_offset = coff_offset + 20;
+/// Full PE header information: enough for Wine compatibility decisions
+/// beyond the basic `(machine, subsystem, entry_point_rva)` triple.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeInfo {
+    pub machine: u16,
+    pub subsystem: u16,
+    pub entry_point_rva: u32,
+    pub data_directories: Vec<DataDirectory>,
+    pub sections: Vec<SectionHeader>,
+    pub imported_dlls: Vec<String>,
+    pub is_dotnet: bool,
+}
 
-    if optional_size < 0x46 {
-        return None;
+fn rva_to_offset(sections: &[SectionHeader], rva: u32) -> Option<u64> {
+    let section = sections.iter().find(|section| section.contains_rva(rva))?;
+    let delta = rva - section.virtual_address;
+    Some(section.raw_address as u64 + delta as u64)
+}
+
+fn collect_imported_dlls<R: Read + Seek>(
+    reader: &mut R,
+    sections: &[SectionHeader],
+    import_dir: DataDirectory,
+) -> Vec<String> {
+    if import_dir.rva == 0 || import_dir.size == 0 {
+        return Vec::new();
     }
 
-    let magic = read_u16_le(bytes, optional_offset)?;
-    if magic != 0x10B && magic != 0x20B {
-        return None;
+    let Some(mut descriptor_offset) = rva_to_offset(sections, import_dir.rva) else {
+        return Vec::new();
+    };
+
+    let mut dlls = Vec::new();
+    loop {
+        if seek_to(reader, descriptor_offset + 12).is_err() {
+            break;
+        }
+        let Ok(name_rva) = read_u32_le(reader) else {
+            break;
+        };
+        if name_rva == 0 {
+            break;
+        }
+        if let Some(name_offset) = rva_to_offset(sections, name_rva) {
+            if let Some(name) = read_cstr_ascii(reader, name_offset) {
+                dlls.push(name);
+            }
+        }
+        descriptor_offset += IMPORT_DESCRIPTOR_SIZE;
     }
 
-    let entry_point_rva = read_u32_le(bytes, optional_offset + 0x10)?;
-    let subsystem = read_u16_le(bytes, optional_offset + 0x44)?;
+    dlls
+}
+
+impl FromReader for PeInfo {
+    fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<PeInfo, PeParseError> {
+        seek_to(reader, 0x3C)?;
+        let pe_offset = read_u32_le(reader)? as u64;
+
+        seek_to(reader, pe_offset)?;
+        let mut signature = [0u8; 4];
+        reader.read_exact(&mut signature).map_err(|_| PeParseError::Truncated)?;
+        if &signature != b"PE\0\0" {
+            return Err(PeParseError::NotPe);
+        }
+
+        let coff_offset = pe_offset + 4;
+        seek_to(reader, coff_offset)?;
+        let machine = read_u16_le(reader)?;
+        let number_of_sections = read_u16_le(reader)? as usize;
+
+        seek_to(reader, coff_offset + 16)?;
+        let optional_size = read_u16_le(reader)? as usize;
+        let optional_offset = coff_offset + 20;
+
+        if optional_size < 0x46 {
+            return Err(PeParseError::Truncated);
+        }
+
+        seek_to(reader, optional_offset)?;
+        let magic = read_u16_le(reader)?;
+        let is_pe32_plus = match magic {
+            0x10B => false,
+            0x20B => true,
+            _ => return Err(PeParseError::UnsupportedMagic),
+        };
+
+        seek_to(reader, optional_offset + 0x10)?;
+        let entry_point_rva = read_u32_le(reader)?;
+
+        seek_to(reader, optional_offset + 0x44)?;
+        let subsystem = read_u16_le(reader)?;
+
+        let number_of_rva_and_sizes_offset = optional_offset + if is_pe32_plus { 0x6C } else { 0x5C };
+        seek_to(reader, number_of_rva_and_sizes_offset)?;
+        let number_of_rva_and_sizes =
+            read_u32_le(reader)?.min(IMAGE_NUMBEROF_DIRECTORY_ENTRIES as u32) as usize;
+
+        let data_directory_offset = number_of_rva_and_sizes_offset + 4;
+        seek_to(reader, data_directory_offset)?;
+        let mut data_directories = Vec::with_capacity(IMAGE_NUMBEROF_DIRECTORY_ENTRIES);
+        for _ in 0..number_of_rva_and_sizes {
+            data_directories.push(DataDirectory {
+                rva: read_u32_le(reader)?,
+                size: read_u32_le(reader)?,
+            });
+        }
+        data_directories.resize(IMAGE_NUMBEROF_DIRECTORY_ENTRIES, DataDirectory::default());
+
+        let section_table_offset = optional_offset + optional_size as u64;
+        let mut sections = Vec::with_capacity(number_of_sections);
+        for index in 0..number_of_sections {
+            let entry_offset = section_table_offset + (index * SECTION_HEADER_SIZE) as u64;
+            sections.push(SectionHeader::read_at(reader, entry_offset)?);
+        }
+
+        let is_dotnet = data_directories
+            .get(IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR)
+            .map(|dir| dir.size != 0)
+            .unwrap_or(false);
+
+        let imported_dlls = data_directories
+            .get(IMAGE_DIRECTORY_ENTRY_IMPORT)
+            .copied()
+            .map(|dir| collect_imported_dlls(reader, &sections, dir))
+            .unwrap_or_default();
+
+        Ok(PeInfo {
+            machine,
+            subsystem,
+            entry_point_rva,
+            data_directories,
+            sections,
+            imported_dlls,
+            is_dotnet,
+        })
+    }
+}
+
+pub fn inspect_pe_bytes(bytes: &[u8]) -> Option<(u16, u16, u32)> {
+    let info = inspect_pe_info_bytes(bytes)?;
+    Some((info.machine, info.subsystem, info.entry_point_rva))
+}
 
-    Some((machine, subsystem, entry_point_rva))
+pub fn inspect_pe_info_bytes(bytes: &[u8]) -> Option<PeInfo> {
+    inspect_pe_info_bytes_detailed(bytes).ok()
+}
+
+pub fn inspect_pe_info_bytes_detailed(bytes: &[u8]) -> Result<PeInfo, PeParseError> {
+    let mut cursor = Cursor::new(bytes);
+    PeInfo::from_reader(&mut cursor)
 }
 
 pub fn inspect_pe_path(path: &Path) -> Option<(u16, u16, u32)> {
-    let bytes = fs::read(path).ok()?;
-    inspect_pe_bytes(&bytes)
+    let info = inspect_pe_info_path(path)?;
+    Some((info.machine, info.subsystem, info.entry_point_rva))
+}
+
+pub fn inspect_pe_info_path(path: &Path) -> Option<PeInfo> {
+    inspect_pe_info_path_detailed(path).ok()
+}
+
+pub fn inspect_pe_info_path_detailed(path: &Path) -> Result<PeInfo, PeOpenError> {
+    let file = File::open(path).map_err(PeOpenError::Io)?;
+    let mut reader = BufReader::new(file);
+    PeInfo::from_reader(&mut reader).map_err(PeOpenError::Parse)
+}
+
+/// Same as [`inspect_pe_info_path`], but maps the file into memory instead
+/// of going through buffered reads. Worthwhile for very large binaries that
+/// will be inspected more than once, since the OS page cache backs repeat
+/// accesses instead of re-reading from disk.
+#[cfg(feature = "mmap")]
+pub fn inspect_pe_info_path_mmap(path: &Path) -> Option<PeInfo> {
+    let file = File::open(path).ok()?;
+    let mmap = unsafe { Mmap::map(&file).ok()? };
+    let mut cursor = Cursor::new(&mmap[..]);
+    PeInfo::from_reader(&mut cursor).ok()
 }
 
 #[cfg(test)]
@@ -86,4 +333,97 @@ mod tests {
         pe[0x80] = b'X';
         assert!(inspect_pe_bytes(&pe).is_none());
     }
+
+    #[test]
+    fn classifies_not_pe_and_truncated_errors() {
+        let mut pe = build_minimal_pe();
+        pe[0x80] = b'X';
+        assert_eq!(
+            inspect_pe_info_bytes_detailed(&pe),
+            Err(PeParseError::NotPe)
+        );
+
+        let truncated = vec![0u8; 0x10];
+        assert_eq!(
+            inspect_pe_info_bytes_detailed(&truncated),
+            Err(PeParseError::Truncated)
+        );
+    }
+
+    fn build_pe_with_import(dll_name: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x400];
+
+        bytes[0] = b'M';
+        bytes[1] = b'Z';
+
+        let pe_offset: u32 = 0x80;
+        bytes[0x3C..0x40].copy_from_slice(&pe_offset.to_le_bytes());
+        bytes[0x80..0x84].copy_from_slice(b"PE\0\0");
+
+        let coff = 0x84;
+        bytes[coff..coff + 2].copy_from_slice(&0x8664u16.to_le_bytes());
+        bytes[coff + 2..coff + 4].copy_from_slice(&1u16.to_le_bytes()); // one section
+        let optional_size: u16 = 0xF0;
+        bytes[coff + 16..coff + 18].copy_from_slice(&optional_size.to_le_bytes());
+
+        let optional = coff + 20;
+        bytes[optional..optional + 2].copy_from_slice(&0x20Bu16.to_le_bytes());
+        bytes[optional + 0x10..optional + 0x14].copy_from_slice(&0x1000u32.to_le_bytes());
+        bytes[optional + 0x44..optional + 0x46].copy_from_slice(&2u16.to_le_bytes());
+
+        let number_of_rva_and_sizes_offset = optional + 0x6C;
+        bytes[number_of_rva_and_sizes_offset..number_of_rva_and_sizes_offset + 4]
+            .copy_from_slice(&16u32.to_le_bytes());
+
+        let data_directory_offset = number_of_rva_and_sizes_offset + 4;
+        let import_dir_offset = data_directory_offset + IMAGE_DIRECTORY_ENTRY_IMPORT * 8;
+        bytes[import_dir_offset..import_dir_offset + 4].copy_from_slice(&0x2000u32.to_le_bytes());
+        bytes[import_dir_offset + 4..import_dir_offset + 8].copy_from_slice(&0x100u32.to_le_bytes());
+
+        let section_table_offset = optional + optional_size as usize;
+        bytes[section_table_offset..section_table_offset + 5].copy_from_slice(b".text");
+        bytes[section_table_offset + 8..section_table_offset + 12]
+            .copy_from_slice(&0x3000u32.to_le_bytes()); // virtual size
+        bytes[section_table_offset + 12..section_table_offset + 16]
+            .copy_from_slice(&0x2000u32.to_le_bytes()); // virtual address
+        bytes[section_table_offset + 16..section_table_offset + 20]
+            .copy_from_slice(&0x3000u32.to_le_bytes()); // raw size
+        bytes[section_table_offset + 20..section_table_offset + 24]
+            .copy_from_slice(&0x300u32.to_le_bytes()); // raw address
+
+        // import descriptor at RVA 0x2000 -> file offset 0x300 + (0x2000 - 0x2000) = 0x300
+        let descriptor_offset = 0x300usize;
+        let name_rva = 0x2050u32;
+        bytes[descriptor_offset + 12..descriptor_offset + 16].copy_from_slice(&name_rva.to_le_bytes());
+        // terminator descriptor follows, already zeroed
+
+        let name_offset = 0x300 + (0x2050 - 0x2000);
+        bytes[name_offset..name_offset + dll_name.len()].copy_from_slice(dll_name);
+
+        bytes
+    }
+
+    #[test]
+    fn collects_imported_dll_names() {
+        let pe = build_pe_with_import(b"KERNEL32.dll\0");
+        let info = inspect_pe_info_bytes(&pe).expect("should parse");
+        assert_eq!(info.imported_dlls, vec!["KERNEL32.dll".to_string()]);
+        assert!(!info.is_dotnet);
+        assert_eq!(info.sections.len(), 1);
+        assert_eq!(info.sections[0].name, ".text");
+    }
+
+    #[test]
+    fn inspect_pe_path_matches_inspect_pe_bytes() {
+        let pe = build_minimal_pe();
+        let dir = std::env::temp_dir();
+        let path = dir.join("vodka_pe_reader_test.exe");
+        std::fs::write(&path, &pe).expect("write temp pe");
+
+        let from_path = inspect_pe_path(&path);
+        let from_bytes = inspect_pe_bytes(&pe);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(from_path, from_bytes);
+    }
 }
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::WineVersion;
+
+/// Bottle/runtime settings, persisted as TOML so the same file can be read
+/// by both the Rust core and any external tooling.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BottleConfig {
+    pub wine_version: Option<String>,
+    #[serde(default)]
+    pub dxvk_enabled: bool,
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    pub windows_version: Option<String>,
+}
+
+/// Result of [`BottleConfigFile::save`]: whether the file was actually
+/// rewritten, already matched what's on disk, or was left alone because it
+/// changed on disk since it was last read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveOutcome {
+    Written,
+    Unchanged,
+    StaleOnDisk,
+}
+
+/// A `BottleConfig` bound to a TOML file on disk, tracking the file's mtime
+/// at load time so a later save can detect hand edits made in between and
+/// refuse to clobber them.
+#[derive(Debug)]
+pub struct BottleConfigFile {
+    path: PathBuf,
+    config: BottleConfig,
+    loaded_mtime: Option<SystemTime>,
+}
+
+impl BottleConfigFile {
+    /// Loads the config from `path`, or starts from defaults if the file
+    /// doesn't exist yet.
+    pub fn load(path: &Path) -> io::Result<BottleConfigFile> {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                let config = toml::from_str(&contents)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                Ok(BottleConfigFile {
+                    path: path.to_path_buf(),
+                    config,
+                    loaded_mtime: file_mtime(path),
+                })
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(BottleConfigFile {
+                path: path.to_path_buf(),
+                config: BottleConfig::default(),
+                loaded_mtime: None,
+            }),
+            Err(error) => Err(error),
+        }
+    }
+
+    pub fn config(&self) -> &BottleConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut BottleConfig {
+        &mut self.config
+    }
+
+    /// The configured Wine version tag, normalized through
+    /// [`WineVersion::parse`] so callers never deal with raw tag strings.
+    pub fn wine_version(&self) -> Option<WineVersion> {
+        self.config.wine_version.as_deref().and_then(WineVersion::parse)
+    }
+
+    /// Writes the config back to its file, unless the serialized form is
+    /// identical to what's already there, or the file was modified on disk
+    /// since it was last read (in which case the caller should reload
+    /// before trying again).
+    pub fn save(&mut self) -> io::Result<SaveOutcome> {
+        if let (Some(loaded), Some(current)) = (self.loaded_mtime, file_mtime(&self.path)) {
+            if loaded != current {
+                return Ok(SaveOutcome::StaleOnDisk);
+            }
+        }
+
+        let serialized = toml::to_string_pretty(&self.config)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+        if let Ok(existing) = fs::read_to_string(&self.path) {
+            if existing == serialized {
+                return Ok(SaveOutcome::Unchanged);
+            }
+        }
+
+        fs::write(&self.path, &serialized)?;
+        self.loaded_mtime = file_mtime(&self.path);
+        Ok(SaveOutcome::Written)
+    }
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("vodka_runtime_config_test_{name}.toml"))
+    }
+
+    #[test]
+    fn loads_defaults_when_file_missing() {
+        let path = temp_config_path("missing");
+        let _ = fs::remove_file(&path);
+
+        let file = BottleConfigFile::load(&path).expect("load should not fail for a missing file");
+        assert_eq!(file.config(), &BottleConfig::default());
+    }
+
+    #[test]
+    fn save_writes_then_reports_unchanged() {
+        let path = temp_config_path("roundtrip");
+        let _ = fs::remove_file(&path);
+
+        let mut file = BottleConfigFile::load(&path).unwrap();
+        file.config_mut().wine_version = Some("wine-8.0.1".to_string());
+        file.config_mut().dxvk_enabled = true;
+
+        assert_eq!(file.save().unwrap(), SaveOutcome::Written);
+        assert_eq!(file.save().unwrap(), SaveOutcome::Unchanged);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn normalizes_wine_version_on_load() {
+        let path = temp_config_path("normalize");
+        fs::write(&path, "wine_version = \"wine-8.0.1\"\n").unwrap();
+
+        let file = BottleConfigFile::load(&path).unwrap();
+        assert_eq!(file.wine_version().unwrap().numeric_string(), "8.0.1");
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn refuses_to_overwrite_externally_modified_file() {
+        let path = temp_config_path("stale");
+        let _ = fs::remove_file(&path);
+
+        let mut file = BottleConfigFile::load(&path).unwrap();
+        file.save().unwrap();
+
+        // Simulate a hand edit landing after the in-memory config was loaded.
+        file.loaded_mtime = Some(SystemTime::UNIX_EPOCH);
+        file.config_mut().dxvk_enabled = true;
+
+        assert_eq!(file.save().unwrap(), SaveOutcome::StaleOnDisk);
+
+        let _ = fs::remove_file(&path);
+    }
+}
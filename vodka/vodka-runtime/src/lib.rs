@@ -1,30 +1,173 @@
-pub fn normalize_wine_tag(input: &str) -> Option<String> {
-    let mut value = input.trim();
-    if value.is_empty() {
-        return None;
+use std::cmp::Ordering;
+
+pub mod config;
+
+/// How a Wine/Proton tag's numeric version is qualified: still in testing,
+/// a hardened staging patchset, a final release, a Proton/GE build with its
+/// own sub-version scheme, or an unreleased devel snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionSuffix {
+    Staging,
+    ReleaseCandidate(u32),
+    Stable,
+    Devel,
+    Proton { generation: u32, sub_version: u32 },
+}
+
+impl VersionSuffix {
+    /// Ascending rank used for comparisons: release candidates sort before
+    /// their final release, stable sorts after staging, devel tracks ahead
+    /// of stable, and Proton/GE builds are their own scheme ranked last.
+    fn rank(self) -> (u8, u32, u32) {
+        match self {
+            VersionSuffix::Staging => (0, 0, 0),
+            VersionSuffix::ReleaseCandidate(n) => (1, n, 0),
+            VersionSuffix::Stable => (2, 0, 0),
+            VersionSuffix::Devel => (3, 0, 0),
+            VersionSuffix::Proton { generation, sub_version } => (4, generation, sub_version),
+        }
+    }
+}
+
+/// A parsed, comparable Wine or Proton version tag, e.g. `wine-8.0.1-rc1`,
+/// `lutris-GE-Proton8-26`, or `v9.21`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WineVersion {
+    pub components: Vec<u32>,
+    pub suffix: VersionSuffix,
+}
+
+impl WineVersion {
+    pub fn parse(input: &str) -> Option<WineVersion> {
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        if let Some(proton) = parse_proton_version(trimmed) {
+            return Some(proton);
+        }
+
+        let mut value = trimmed;
+        if let Some(stripped) = value.strip_prefix("wine-") {
+            value = stripped;
+        }
+        if let Some(stripped) = value.strip_prefix('v') {
+            value = stripped;
+        }
+
+        let mut numeric_end = 0;
+        for (index, ch) in value.char_indices() {
+            if ch.is_ascii_digit() || ch == '.' {
+                numeric_end = index + ch.len_utf8();
+            } else {
+                break;
+            }
+        }
+
+        let numeric_part = &value[..numeric_end];
+        if numeric_part.is_empty() {
+            return None;
+        }
+
+        let components: Vec<u32> = numeric_part
+            .split('.')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| segment.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        if components.is_empty() {
+            return None;
+        }
+
+        let suffix = parse_suffix(&value[numeric_end..]);
+
+        Some(WineVersion { components, suffix })
     }
 
-    if let Some(stripped) = value.strip_prefix("wine-") {
-        value = stripped;
+    /// The numeric version only, e.g. `8.0.1`, discarding the suffix.
+    pub fn numeric_string(&self) -> String {
+        self.components
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(".")
     }
-    if let Some(stripped) = value.strip_prefix('v') {
-        value = stripped;
+
+    fn sort_key(&self) -> (&[u32], (u8, u32, u32)) {
+        (&self.components, self.suffix.rank())
+    }
+}
+
+impl PartialOrd for WineVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WineVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+fn parse_suffix(rest: &str) -> VersionSuffix {
+    let rest = rest.trim_start_matches('-');
+    let lower = rest.to_ascii_lowercase();
+
+    if lower.is_empty() {
+        VersionSuffix::Stable
+    } else if let Some(number) = lower.strip_prefix("rc") {
+        VersionSuffix::ReleaseCandidate(number.parse().unwrap_or(0))
+    } else if lower.starts_with("staging") {
+        VersionSuffix::Staging
+    } else if lower.starts_with("devel") || lower.starts_with("dev") {
+        VersionSuffix::Devel
+    } else {
+        VersionSuffix::Stable
     }
+}
+
+fn parse_proton_version(trimmed: &str) -> Option<WineVersion> {
+    let lower = trimmed.to_ascii_lowercase();
+    let proton_index = lower.find("proton")?;
+    let after = &trimmed[proton_index + "proton".len()..];
 
-    let mut normalized = String::new();
-    for ch in value.chars() {
-        if ch.is_ascii_digit() || ch == '.' {
-            normalized.push(ch);
+    let mut generation_end = 0;
+    for (index, ch) in after.char_indices() {
+        if ch.is_ascii_digit() {
+            generation_end = index + ch.len_utf8();
         } else {
             break;
         }
     }
+    let generation: u32 = after[..generation_end].parse().ok()?;
 
-    if normalized.is_empty() {
-        None
-    } else {
-        Some(normalized)
+    let rest = after[generation_end..].trim_start_matches('-');
+    let mut sub_version_end = 0;
+    for (index, ch) in rest.char_indices() {
+        if ch.is_ascii_digit() {
+            sub_version_end = index + ch.len_utf8();
+        } else {
+            break;
+        }
     }
+    let sub_version: u32 = if sub_version_end > 0 {
+        rest[..sub_version_end].parse().ok()?
+    } else {
+        0
+    };
+
+    Some(WineVersion {
+        components: vec![generation, sub_version],
+        suffix: VersionSuffix::Proton { generation, sub_version },
+    })
+}
+
+/// Convenience wrapper over [`WineVersion::parse`] that returns only the
+/// numeric part of the tag, discarding any release-candidate/staging/Proton
+/// qualifier.
+pub fn normalize_wine_tag(input: &str) -> Option<String> {
+    WineVersion::parse(input).map(|version| version.numeric_string())
 }
 
 #[cfg(test)]
@@ -53,4 +196,50 @@ mod tests {
     fn rejects_non_numeric() {
         assert_eq!(normalize_wine_tag("release"), None);
     }
+
+    #[test]
+    fn parses_release_candidate_suffix() {
+        let version = WineVersion::parse("wine-8.0.1-rc1").unwrap();
+        assert_eq!(version.components, vec![8, 0, 1]);
+        assert_eq!(version.suffix, VersionSuffix::ReleaseCandidate(1));
+    }
+
+    #[test]
+    fn parses_staging_suffix() {
+        let version = WineVersion::parse("wine-9.0-staging").unwrap();
+        assert_eq!(version.suffix, VersionSuffix::Staging);
+    }
+
+    #[test]
+    fn parses_proton_tag() {
+        let version = WineVersion::parse("lutris-GE-Proton8-26").unwrap();
+        assert_eq!(
+            version.suffix,
+            VersionSuffix::Proton {
+                generation: 8,
+                sub_version: 26,
+            }
+        );
+    }
+
+    #[test]
+    fn release_candidate_sorts_before_final_release() {
+        let rc = WineVersion::parse("wine-8.0-rc1").unwrap();
+        let stable = WineVersion::parse("wine-8.0").unwrap();
+        assert!(rc < stable);
+    }
+
+    #[test]
+    fn stable_sorts_after_staging_for_same_version() {
+        let staging = WineVersion::parse("wine-8.0-staging").unwrap();
+        let stable = WineVersion::parse("wine-8.0").unwrap();
+        assert!(staging < stable);
+    }
+
+    #[test]
+    fn newer_numeric_version_always_sorts_higher() {
+        let older = WineVersion::parse("wine-8.0.1").unwrap();
+        let newer = WineVersion::parse("wine-9.0").unwrap();
+        assert!(older < newer);
+    }
 }